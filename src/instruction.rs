@@ -0,0 +1,35 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub enum EchoInstruction {
+    Echo {
+        data: Vec<u8>,
+    },
+    InitializeAuthorizedEcho {
+        buffer_seed: u64,
+        buffer_size: usize,
+    },
+    AuthorizedEcho {
+        data: Vec<u8>,
+    },
+    InitializeVendingMachine {
+        price: u64,
+        buffer_size: usize,
+    },
+    ResizeAuthorizedBuffer {
+        buffer_seed: u64,
+        new_size: usize,
+    },
+    SetAuthority {
+        buffer_seed: u64,
+    },
+    VendingMachineEcho {
+        data: Vec<u8>,
+    },
+    CloseBuffer {
+        buffer_seed: u64,
+    },
+    CloseVendingMachineBuffer {
+        price: u64,
+    },
+}
@@ -0,0 +1,3 @@
+pub mod instruction;
+pub mod processor;
+pub mod state;
@@ -3,7 +3,7 @@ use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     msg,
-    program::invoke_signed,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
@@ -14,7 +14,67 @@ use solana_program::{
 use spl_token::state::Mint;
 
 use crate::instruction::EchoInstruction;
-use crate::state::{AuthorizedBufferHeader, VendingMachineBufferHeader, AUTH_BUFFER_HEADER_SIZE};
+use crate::state::{
+    AuthorizedBufferHeader, VendingMachineBufferHeader, AUTH_BUFFER_HEADER_SIZE,
+    VENDING_MACHINE_HEADER_SIZE,
+};
+
+/// Mirrors `solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE`; a single
+/// resize can only grow an account by this many bytes.
+const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+
+/// Recomputes the authorized-buffer PDA from its header and checks it against the
+/// account actually passed in. Seeds are derived from `seed_authority`, not the
+/// current `authority`, so this stays correct across `SetAuthority` transfers.
+fn verify_authorized_buffer_pda(
+    program_id: &Pubkey,
+    buffer_header: &AuthorizedBufferHeader,
+    authorized_buffer_key: &Pubkey,
+) -> Result<(), ProgramError> {
+    let pda = Pubkey::create_program_address(
+        &[
+            b"authority",
+            buffer_header.seed_authority.as_ref(),
+            &buffer_header.buffer_seed.to_le_bytes(),
+            &[buffer_header.bump_seed],
+        ],
+        program_id,
+    )
+    .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if pda != *authorized_buffer_key {
+        msg!("authorized buffer is not correct pda");
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// Recomputes the vending-machine buffer PDA from its header and checks it against
+/// the account actually passed in.
+fn verify_vending_machine_pda(
+    program_id: &Pubkey,
+    mint_key: &Pubkey,
+    buffer_header: &VendingMachineBufferHeader,
+    vending_machine_buffer_key: &Pubkey,
+) -> Result<(), ProgramError> {
+    let pda = Pubkey::create_program_address(
+        &[
+            b"vending_machine",
+            mint_key.as_ref(),
+            &buffer_header.price.to_le_bytes(),
+            &[buffer_header.bump_seed],
+        ],
+        program_id,
+    )
+    .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if pda != *vending_machine_buffer_key {
+        msg!("vending machine buffer is not correct pda");
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
 pub struct Processor {}
 
 impl Processor {
@@ -39,7 +99,7 @@ impl Processor {
                 }
                 let bytes_to_copy = buffer.len();
                 for index in 0..bytes_to_copy {
-                    buffer[index] = data[index]
+                    buffer[index] = data.get(index).copied().unwrap_or(0);
                 }
                 msg!(
                     "Successfully wrote {} bytes to account of size {}",
@@ -104,6 +164,8 @@ impl Processor {
                 let buffer_header = AuthorizedBufferHeader {
                     bump_seed,
                     buffer_seed,
+                    seed_authority: *authority.key,
+                    authority: *authority.key,
                 };
 
                 buffer[0..AUTH_BUFFER_HEADER_SIZE]
@@ -117,25 +179,22 @@ impl Processor {
                 let accounts_iter = &mut accounts.iter();
                 let authorized_buffer = next_account_info(accounts_iter)?;
                 let authority = next_account_info(accounts_iter)?;
-                let buffer = &mut (*authorized_buffer.data).try_borrow_mut().unwrap();
+                let buffer = &mut (*authorized_buffer.data)
+                    .try_borrow_mut()
+                    .map_err(|_| ProgramError::AccountBorrowFailed)?;
+                if buffer.len() < AUTH_BUFFER_HEADER_SIZE {
+                    msg!("authorized buffer is smaller than the header");
+                    return Err(ProgramError::AccountDataTooSmall);
+                }
                 let buffer_header =
                     AuthorizedBufferHeader::try_from_slice(&buffer[..AUTH_BUFFER_HEADER_SIZE])
-                        .unwrap();
+                        .map_err(|_| ProgramError::InvalidAccountData)?;
 
-                let pda = Pubkey::create_program_address(
-                    &[
-                        b"authority",
-                        authority.key.as_ref(),
-                        &buffer_header.buffer_seed.to_le_bytes(),
-                        &[buffer_header.bump_seed],
-                    ],
-                    _program_id,
-                )
-                .unwrap();
+                verify_authorized_buffer_pda(_program_id, &buffer_header, authorized_buffer.key)?;
 
-                if pda != *authorized_buffer.key {
-                    msg!("authorized buffer is not correct pda");
-                    return Err(ProgramError::IllegalOwner);
+                if !authority.is_signer || buffer_header.authority != *authority.key {
+                    msg!("signer is not the buffer's authority");
+                    return Err(ProgramError::MissingRequiredSignature);
                 }
 
                 let buffer_data = &mut buffer[AUTH_BUFFER_HEADER_SIZE..];
@@ -147,8 +206,144 @@ impl Processor {
                     };
                 }
             }
+            EchoInstruction::ResizeAuthorizedBuffer {
+                buffer_seed,
+                new_size,
+            } => {
+                msg!("Resize authorized buffer");
+                let accounts_iter = &mut accounts.iter();
+                let authorized_buffer = next_account_info(accounts_iter)?;
+                let authority = next_account_info(accounts_iter)?;
+                let system_program = next_account_info(accounts_iter)?;
+
+                if !authority.is_signer {
+                    msg!("authority did not sign");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                let old_size = authorized_buffer.data_len();
+                if old_size < AUTH_BUFFER_HEADER_SIZE {
+                    msg!("authorized buffer is smaller than the header");
+                    return Err(ProgramError::AccountDataTooSmall);
+                }
+
+                let buffer_header = AuthorizedBufferHeader::try_from_slice(
+                    &authorized_buffer.data.borrow()[..AUTH_BUFFER_HEADER_SIZE],
+                )
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+
+                if buffer_header.buffer_seed != buffer_seed {
+                    msg!("buffer_seed does not match the buffer's header");
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                verify_authorized_buffer_pda(_program_id, &buffer_header, authorized_buffer.key)?;
+
+                if buffer_header.authority != *authority.key {
+                    msg!("signer is not the buffer's authority");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                if new_size < AUTH_BUFFER_HEADER_SIZE {
+                    msg!(
+                        "Invalid new_size {}, must be at least header size {}",
+                        new_size,
+                        AUTH_BUFFER_HEADER_SIZE
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                if new_size > old_size && new_size - old_size > MAX_PERMITTED_DATA_INCREASE {
+                    msg!(
+                        "Cannot grow by more than {} bytes in a single resize",
+                        MAX_PERMITTED_DATA_INCREASE
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                let rent = Rent::get()?;
+                let new_minimum_balance = rent.minimum_balance(new_size);
+                let old_minimum_balance = rent.minimum_balance(old_size);
+
+                if new_size > old_size {
+                    let lamports_diff = new_minimum_balance.saturating_sub(old_minimum_balance);
+                    if lamports_diff > 0 {
+                        invoke(
+                            &system_instruction::transfer(
+                                authority.key,
+                                authorized_buffer.key,
+                                lamports_diff,
+                            ),
+                            &[
+                                authority.clone(),
+                                authorized_buffer.clone(),
+                                system_program.clone(),
+                            ],
+                        )?;
+                    }
+                    authorized_buffer.realloc(new_size, true)?;
+                } else {
+                    authorized_buffer.realloc(new_size, false)?;
+                    let lamports_diff = old_minimum_balance.saturating_sub(new_minimum_balance);
+                    if lamports_diff > 0 {
+                        **authorized_buffer.try_borrow_mut_lamports()? -= lamports_diff;
+                        **authority.try_borrow_mut_lamports()? += lamports_diff;
+                    }
+                }
+
+                msg!("Resized authorized buffer from {} to {}", old_size, new_size);
+            }
+            EchoInstruction::SetAuthority { buffer_seed } => {
+                msg!("Set authority");
+                let accounts_iter = &mut accounts.iter();
+                let authorized_buffer = next_account_info(accounts_iter)?;
+                let authority = next_account_info(accounts_iter)?;
+                let new_authority = next_account_info(accounts_iter)?;
+
+                if !authority.is_signer || !new_authority.is_signer {
+                    msg!("both the current authority and the new authority must sign");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                let buffer = &mut (*authorized_buffer.data)
+                    .try_borrow_mut()
+                    .map_err(|_| ProgramError::AccountBorrowFailed)?;
+                if buffer.len() < AUTH_BUFFER_HEADER_SIZE {
+                    msg!("authorized buffer is smaller than the header");
+                    return Err(ProgramError::AccountDataTooSmall);
+                }
+
+                let mut buffer_header =
+                    AuthorizedBufferHeader::try_from_slice(&buffer[..AUTH_BUFFER_HEADER_SIZE])
+                        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+                if buffer_header.buffer_seed != buffer_seed {
+                    msg!("buffer_seed does not match the buffer's header");
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                verify_authorized_buffer_pda(_program_id, &buffer_header, authorized_buffer.key)?;
+
+                if buffer_header.authority != *authority.key {
+                    msg!("signer is not the buffer's current authority");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                buffer_header.authority = *new_authority.key;
+                buffer[0..AUTH_BUFFER_HEADER_SIZE]
+                    .copy_from_slice(&buffer_header.try_to_vec().unwrap());
+                msg!("Authority transferred to {}", new_authority.key);
+            }
             EchoInstruction::InitializeVendingMachine { price, buffer_size } => {
                 msg!("Initialize vending machine");
+                if buffer_size <= VENDING_MACHINE_HEADER_SIZE {
+                    msg!(
+                        "Invalid buffer length {}, must be greater than header size {}",
+                        buffer_size,
+                        VENDING_MACHINE_HEADER_SIZE
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
                 let accounts_iter = &mut accounts.iter();
                 let vending_machine_buffer = next_account_info(accounts_iter)?;
                 let vending_machine_mint = next_account_info(accounts_iter)?;
@@ -204,15 +399,188 @@ impl Processor {
                 let vending_machine_buffer_header = VendingMachineBufferHeader {
                     bump_seed: bump,
                     price: price,
+                    authority: *payer.key,
                 };
 
-                buffer[0..AUTH_BUFFER_HEADER_SIZE]
+                buffer[0..VENDING_MACHINE_HEADER_SIZE]
                     .copy_from_slice(&vending_machine_buffer_header.try_to_vec().unwrap());
 
                 msg!("Vending machine buffer len: {}", buffer_size);
                 msg!("Bump seed: {}", bump);
                 msg!("Buffer price: {}", price);
             }
+            EchoInstruction::VendingMachineEcho { data } => {
+                msg!("Vending machine echo");
+                let accounts_iter = &mut accounts.iter();
+                let vending_machine_buffer = next_account_info(accounts_iter)?;
+                let vending_machine_mint = next_account_info(accounts_iter)?;
+                let payer_token_account = next_account_info(accounts_iter)?;
+                let payer = next_account_info(accounts_iter)?;
+                let token_program = next_account_info(accounts_iter)?;
+
+                if token_program.key != &spl_token::id() {
+                    msg!("token_program is not the SPL Token program");
+                    return Err(ProgramError::IncorrectProgramId);
+                }
+
+                if vending_machine_buffer.data_len() < VENDING_MACHINE_HEADER_SIZE {
+                    msg!("vending machine buffer is smaller than the header");
+                    return Err(ProgramError::AccountDataTooSmall);
+                }
+
+                let buffer_header = VendingMachineBufferHeader::try_from_slice(
+                    &vending_machine_buffer.data.borrow()[..VENDING_MACHINE_HEADER_SIZE],
+                )
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+
+                verify_vending_machine_pda(
+                    _program_id,
+                    vending_machine_mint.key,
+                    &buffer_header,
+                    vending_machine_buffer.key,
+                )?;
+
+                let burn_ix = spl_token::instruction::burn(
+                    token_program.key,
+                    payer_token_account.key,
+                    vending_machine_mint.key,
+                    payer.key,
+                    &[],
+                    buffer_header.price,
+                )?;
+
+                invoke(
+                    &burn_ix,
+                    &[
+                        payer_token_account.clone(),
+                        vending_machine_mint.clone(),
+                        payer.clone(),
+                        token_program.clone(),
+                    ],
+                )?;
+
+                let buffer = &mut (*vending_machine_buffer.data)
+                    .try_borrow_mut()
+                    .map_err(|_| ProgramError::AccountBorrowFailed)?;
+                let buffer_data = &mut buffer[VENDING_MACHINE_HEADER_SIZE..];
+
+                for index in 0..buffer_data.len() {
+                    buffer_data[index] = data.get(index).copied().unwrap_or(0);
+                }
+
+                msg!("Charged {} tokens and wrote to vending machine buffer", buffer_header.price);
+            }
+            EchoInstruction::CloseBuffer { buffer_seed } => {
+                msg!("Close authorized buffer");
+                let accounts_iter = &mut accounts.iter();
+                let authorized_buffer = next_account_info(accounts_iter)?;
+                let authority = next_account_info(accounts_iter)?;
+                let destination = next_account_info(accounts_iter)?;
+
+                if !authority.is_signer {
+                    msg!("authority did not sign");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                if authorized_buffer.data_len() < AUTH_BUFFER_HEADER_SIZE {
+                    msg!("authorized buffer is smaller than the header");
+                    return Err(ProgramError::AccountDataTooSmall);
+                }
+
+                let buffer_header = AuthorizedBufferHeader::try_from_slice(
+                    &authorized_buffer.data.borrow()[..AUTH_BUFFER_HEADER_SIZE],
+                )
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+
+                if buffer_header.buffer_seed != buffer_seed {
+                    msg!("buffer_seed does not match the buffer's header");
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                verify_authorized_buffer_pda(_program_id, &buffer_header, authorized_buffer.key)?;
+
+                if buffer_header.authority != *authority.key {
+                    msg!("signer is not the buffer's authority");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                let lamports = authorized_buffer.lamports();
+                **authorized_buffer.try_borrow_mut_lamports()? -= lamports;
+                **destination.try_borrow_mut_lamports()? += lamports;
+
+                let mut buffer = authorized_buffer
+                    .data
+                    .try_borrow_mut()
+                    .map_err(|_| ProgramError::AccountBorrowFailed)?;
+                for byte in buffer.iter_mut() {
+                    *byte = 0;
+                }
+                drop(buffer);
+                authorized_buffer.realloc(0, false)?;
+                authorized_buffer.assign(&solana_program::system_program::id());
+
+                msg!("Closed authorized buffer, reclaimed {} lamports", lamports);
+            }
+            EchoInstruction::CloseVendingMachineBuffer { price } => {
+                msg!("Close vending machine buffer");
+                let accounts_iter = &mut accounts.iter();
+                let vending_machine_buffer = next_account_info(accounts_iter)?;
+                let vending_machine_mint = next_account_info(accounts_iter)?;
+                let authority = next_account_info(accounts_iter)?;
+                let destination = next_account_info(accounts_iter)?;
+
+                if !authority.is_signer {
+                    msg!("authority did not sign");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                if vending_machine_buffer.data_len() < VENDING_MACHINE_HEADER_SIZE {
+                    msg!("vending machine buffer is smaller than the header");
+                    return Err(ProgramError::AccountDataTooSmall);
+                }
+
+                let buffer_header = VendingMachineBufferHeader::try_from_slice(
+                    &vending_machine_buffer.data.borrow()[..VENDING_MACHINE_HEADER_SIZE],
+                )
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+
+                if buffer_header.price != price {
+                    msg!("price does not match the buffer's header");
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                verify_vending_machine_pda(
+                    _program_id,
+                    vending_machine_mint.key,
+                    &buffer_header,
+                    vending_machine_buffer.key,
+                )?;
+
+                if buffer_header.authority != *authority.key {
+                    msg!("signer is not the buffer's authority");
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+
+                let lamports = vending_machine_buffer.lamports();
+                **vending_machine_buffer.try_borrow_mut_lamports()? -= lamports;
+                **destination.try_borrow_mut_lamports()? += lamports;
+
+                let mut buffer = vending_machine_buffer
+                    .data
+                    .try_borrow_mut()
+                    .map_err(|_| ProgramError::AccountBorrowFailed)?;
+                for byte in buffer.iter_mut() {
+                    *byte = 0;
+                }
+                drop(buffer);
+                vending_machine_buffer.realloc(0, false)?;
+                vending_machine_buffer.assign(&solana_program::system_program::id());
+
+                msg!(
+                    "Closed vending machine buffer, reclaimed {} lamports",
+                    lamports
+                );
+            }
             _ => {
                 msg!("invalid instruction");
                 return Err(ProgramError::InvalidInstructionData);
@@ -228,6 +596,535 @@ mod test {
     use solana_program::clock::Epoch;
     use std::{borrow::Borrow, mem};
 
+    #[test]
+    fn test_resize_authorized_buffer_rejects_wrong_signer() {
+        let program_id = Pubkey::default();
+        let seed_authority = Pubkey::new_unique();
+        let impostor = Pubkey::new_unique();
+        let buffer_seed: u64 = 7;
+
+        let (authorized_key, bump_seed) = Pubkey::find_program_address(
+            &[
+                b"authority",
+                seed_authority.as_ref(),
+                &buffer_seed.to_le_bytes(),
+            ],
+            &program_id,
+        );
+
+        let buffer_header = AuthorizedBufferHeader {
+            bump_seed,
+            buffer_seed,
+            seed_authority,
+            authority: seed_authority,
+        };
+        let mut data = vec![0u8; AUTH_BUFFER_HEADER_SIZE + 8];
+        data[..AUTH_BUFFER_HEADER_SIZE].copy_from_slice(&buffer_header.try_to_vec().unwrap());
+
+        let mut buffer_lamports = 100;
+        let authorized_buffer_account = AccountInfo::new(
+            &authorized_key,
+            false,
+            true,
+            &mut buffer_lamports,
+            &mut data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut impostor_lamports = 100;
+        let mut impostor_data = vec![0; mem::size_of::<u32>()];
+        let impostor_account = AccountInfo::new(
+            &impostor,
+            true,
+            false,
+            &mut impostor_lamports,
+            &mut impostor_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let system_id = solana_program::system_program::id();
+        let mut system_lamports = 0;
+        let system_program_account = AccountInfo::new(
+            &system_id,
+            false,
+            false,
+            &mut system_lamports,
+            &mut [],
+            &system_id,
+            false,
+            Epoch::default(),
+        );
+
+        let accounts = vec![
+            authorized_buffer_account,
+            impostor_account,
+            system_program_account,
+        ];
+
+        let mut instruction_data: Vec<u8> = Vec::new();
+        EchoInstruction::ResizeAuthorizedBuffer {
+            buffer_seed,
+            new_size: AUTH_BUFFER_HEADER_SIZE + 16,
+        }
+        .serialize(&mut instruction_data)
+        .unwrap();
+
+        let result = Processor::process_instruction(&program_id, &accounts, &instruction_data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_resize_authorized_buffer_rejects_bad_pda() {
+        let program_id = Pubkey::default();
+        let seed_authority = Pubkey::new_unique();
+        let buffer_seed: u64 = 7;
+
+        let (_correct_key, bump_seed) = Pubkey::find_program_address(
+            &[
+                b"authority",
+                seed_authority.as_ref(),
+                &buffer_seed.to_le_bytes(),
+            ],
+            &program_id,
+        );
+        let wrong_key = Pubkey::new_unique();
+
+        let buffer_header = AuthorizedBufferHeader {
+            bump_seed,
+            buffer_seed,
+            seed_authority,
+            authority: seed_authority,
+        };
+        let mut data = vec![0u8; AUTH_BUFFER_HEADER_SIZE + 8];
+        data[..AUTH_BUFFER_HEADER_SIZE].copy_from_slice(&buffer_header.try_to_vec().unwrap());
+
+        let mut buffer_lamports = 100;
+        let authorized_buffer_account = AccountInfo::new(
+            &wrong_key,
+            false,
+            true,
+            &mut buffer_lamports,
+            &mut data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut authority_lamports = 100;
+        let mut authority_data = vec![0; mem::size_of::<u32>()];
+        let authority_account = AccountInfo::new(
+            &seed_authority,
+            true,
+            false,
+            &mut authority_lamports,
+            &mut authority_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let system_id = solana_program::system_program::id();
+        let mut system_lamports = 0;
+        let system_program_account = AccountInfo::new(
+            &system_id,
+            false,
+            false,
+            &mut system_lamports,
+            &mut [],
+            &system_id,
+            false,
+            Epoch::default(),
+        );
+
+        let accounts = vec![
+            authorized_buffer_account,
+            authority_account,
+            system_program_account,
+        ];
+
+        let mut instruction_data: Vec<u8> = Vec::new();
+        EchoInstruction::ResizeAuthorizedBuffer {
+            buffer_seed,
+            new_size: AUTH_BUFFER_HEADER_SIZE + 16,
+        }
+        .serialize(&mut instruction_data)
+        .unwrap();
+
+        let result = Processor::process_instruction(&program_id, &accounts, &instruction_data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_set_authority_rejects_wrong_authority() {
+        let program_id = Pubkey::default();
+        let seed_authority = Pubkey::new_unique();
+        let impostor = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let buffer_seed: u64 = 4;
+
+        let (authorized_key, bump_seed) = Pubkey::find_program_address(
+            &[
+                b"authority",
+                seed_authority.as_ref(),
+                &buffer_seed.to_le_bytes(),
+            ],
+            &program_id,
+        );
+
+        let buffer_header = AuthorizedBufferHeader {
+            bump_seed,
+            buffer_seed,
+            seed_authority,
+            authority: seed_authority,
+        };
+        let mut data = vec![0u8; AUTH_BUFFER_HEADER_SIZE];
+        data.copy_from_slice(&buffer_header.try_to_vec().unwrap());
+
+        let mut buffer_lamports = 100;
+        let authorized_buffer_account = AccountInfo::new(
+            &authorized_key,
+            false,
+            true,
+            &mut buffer_lamports,
+            &mut data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut impostor_lamports = 100;
+        let mut impostor_data = vec![0; mem::size_of::<u32>()];
+        let impostor_account = AccountInfo::new(
+            &impostor,
+            true,
+            false,
+            &mut impostor_lamports,
+            &mut impostor_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut new_authority_lamports = 100;
+        let mut new_authority_data = vec![0; mem::size_of::<u32>()];
+        let new_authority_account = AccountInfo::new(
+            &new_authority,
+            true,
+            false,
+            &mut new_authority_lamports,
+            &mut new_authority_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let accounts = vec![
+            authorized_buffer_account,
+            impostor_account,
+            new_authority_account,
+        ];
+
+        let mut instruction_data: Vec<u8> = Vec::new();
+        EchoInstruction::SetAuthority { buffer_seed }
+            .serialize(&mut instruction_data)
+            .unwrap();
+
+        let result = Processor::process_instruction(&program_id, &accounts, &instruction_data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_set_authority_rejects_bad_pda() {
+        let program_id = Pubkey::default();
+        let seed_authority = Pubkey::new_unique();
+        let new_authority = Pubkey::new_unique();
+        let buffer_seed: u64 = 4;
+
+        let (_correct_key, bump_seed) = Pubkey::find_program_address(
+            &[
+                b"authority",
+                seed_authority.as_ref(),
+                &buffer_seed.to_le_bytes(),
+            ],
+            &program_id,
+        );
+        let wrong_key = Pubkey::new_unique();
+
+        let buffer_header = AuthorizedBufferHeader {
+            bump_seed,
+            buffer_seed,
+            seed_authority,
+            authority: seed_authority,
+        };
+        let mut data = vec![0u8; AUTH_BUFFER_HEADER_SIZE];
+        data.copy_from_slice(&buffer_header.try_to_vec().unwrap());
+
+        let mut buffer_lamports = 100;
+        let authorized_buffer_account = AccountInfo::new(
+            &wrong_key,
+            false,
+            true,
+            &mut buffer_lamports,
+            &mut data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut authority_lamports = 100;
+        let mut authority_data = vec![0; mem::size_of::<u32>()];
+        let authority_account = AccountInfo::new(
+            &seed_authority,
+            true,
+            false,
+            &mut authority_lamports,
+            &mut authority_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut new_authority_lamports = 100;
+        let mut new_authority_data = vec![0; mem::size_of::<u32>()];
+        let new_authority_account = AccountInfo::new(
+            &new_authority,
+            true,
+            false,
+            &mut new_authority_lamports,
+            &mut new_authority_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let accounts = vec![
+            authorized_buffer_account,
+            authority_account,
+            new_authority_account,
+        ];
+
+        let mut instruction_data: Vec<u8> = Vec::new();
+        EchoInstruction::SetAuthority { buffer_seed }
+            .serialize(&mut instruction_data)
+            .unwrap();
+
+        let result = Processor::process_instruction(&program_id, &accounts, &instruction_data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_vending_machine_echo_rejects_spoofed_token_program() {
+        let program_id = Pubkey::default();
+        let mint = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let price: u64 = 5;
+
+        let (vending_key, bump_seed) = Pubkey::find_program_address(
+            &[b"vending_machine", mint.as_ref(), &price.to_le_bytes()],
+            &program_id,
+        );
+
+        let buffer_header = VendingMachineBufferHeader {
+            bump_seed,
+            price,
+            authority: payer,
+        };
+        let mut data = vec![0u8; VENDING_MACHINE_HEADER_SIZE + 8];
+        data[..VENDING_MACHINE_HEADER_SIZE].copy_from_slice(&buffer_header.try_to_vec().unwrap());
+
+        let mut buffer_lamports = 100;
+        let vending_machine_buffer_account = AccountInfo::new(
+            &vending_key,
+            false,
+            true,
+            &mut buffer_lamports,
+            &mut data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut mint_lamports = 100;
+        let mut mint_data = vec![0; mem::size_of::<u32>()];
+        let mint_account = AccountInfo::new(
+            &mint,
+            false,
+            false,
+            &mut mint_lamports,
+            &mut mint_data,
+            &spl_token::id(),
+            false,
+            Epoch::default(),
+        );
+
+        let payer_token_key = Pubkey::new_unique();
+        let mut payer_token_lamports = 100;
+        let mut payer_token_data = vec![0; mem::size_of::<u32>()];
+        let payer_token_account = AccountInfo::new(
+            &payer_token_key,
+            false,
+            true,
+            &mut payer_token_lamports,
+            &mut payer_token_data,
+            &spl_token::id(),
+            false,
+            Epoch::default(),
+        );
+
+        let mut payer_lamports = 100;
+        let mut payer_data = vec![0; mem::size_of::<u32>()];
+        let payer_account = AccountInfo::new(
+            &payer,
+            true,
+            false,
+            &mut payer_lamports,
+            &mut payer_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let spoofed_token_program = Pubkey::new_unique();
+        let mut token_program_lamports = 0;
+        let spoofed_token_program_account = AccountInfo::new(
+            &spoofed_token_program,
+            false,
+            false,
+            &mut token_program_lamports,
+            &mut [],
+            &spoofed_token_program,
+            false,
+            Epoch::default(),
+        );
+
+        let accounts = vec![
+            vending_machine_buffer_account,
+            mint_account,
+            payer_token_account,
+            payer_account,
+            spoofed_token_program_account,
+        ];
+
+        let mut instruction_data: Vec<u8> = Vec::new();
+        EchoInstruction::VendingMachineEcho {
+            data: vec![1, 2, 3],
+        }
+        .serialize(&mut instruction_data)
+        .unwrap();
+
+        let result = Processor::process_instruction(&program_id, &accounts, &instruction_data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_vending_machine_echo_rejects_bad_pda() {
+        let program_id = Pubkey::default();
+        let mint = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let price: u64 = 5;
+
+        let (_correct_key, bump_seed) = Pubkey::find_program_address(
+            &[b"vending_machine", mint.as_ref(), &price.to_le_bytes()],
+            &program_id,
+        );
+        let wrong_key = Pubkey::new_unique();
+
+        let buffer_header = VendingMachineBufferHeader {
+            bump_seed,
+            price,
+            authority: payer,
+        };
+        let mut data = vec![0u8; VENDING_MACHINE_HEADER_SIZE + 8];
+        data[..VENDING_MACHINE_HEADER_SIZE].copy_from_slice(&buffer_header.try_to_vec().unwrap());
+
+        let mut buffer_lamports = 100;
+        let vending_machine_buffer_account = AccountInfo::new(
+            &wrong_key,
+            false,
+            true,
+            &mut buffer_lamports,
+            &mut data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let mut mint_lamports = 100;
+        let mut mint_data = vec![0; mem::size_of::<u32>()];
+        let mint_account = AccountInfo::new(
+            &mint,
+            false,
+            false,
+            &mut mint_lamports,
+            &mut mint_data,
+            &spl_token::id(),
+            false,
+            Epoch::default(),
+        );
+
+        let payer_token_key = Pubkey::new_unique();
+        let mut payer_token_lamports = 100;
+        let mut payer_token_data = vec![0; mem::size_of::<u32>()];
+        let payer_token_account = AccountInfo::new(
+            &payer_token_key,
+            false,
+            true,
+            &mut payer_token_lamports,
+            &mut payer_token_data,
+            &spl_token::id(),
+            false,
+            Epoch::default(),
+        );
+
+        let mut payer_lamports = 100;
+        let mut payer_data = vec![0; mem::size_of::<u32>()];
+        let payer_account = AccountInfo::new(
+            &payer,
+            true,
+            false,
+            &mut payer_lamports,
+            &mut payer_data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let token_program_id = spl_token::id();
+        let mut token_program_lamports = 0;
+        let token_program_account = AccountInfo::new(
+            &token_program_id,
+            false,
+            false,
+            &mut token_program_lamports,
+            &mut [],
+            &token_program_id,
+            false,
+            Epoch::default(),
+        );
+
+        let accounts = vec![
+            vending_machine_buffer_account,
+            mint_account,
+            payer_token_account,
+            payer_account,
+            token_program_account,
+        ];
+
+        let mut instruction_data: Vec<u8> = Vec::new();
+        EchoInstruction::VendingMachineEcho {
+            data: vec![1, 2, 3],
+        }
+        .serialize(&mut instruction_data)
+        .unwrap();
+
+        let result = Processor::process_instruction(&program_id, &accounts, &instruction_data);
+        assert!(result.is_err());
+    }
+
     // #[test]
     // fn test_initialize_authorize_echo() {
     //     let program_id = Pubkey::default();
@@ -0,0 +1,28 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// `bump_seed: u8` (1 byte) + `buffer_seed: u64` (8 bytes) + `seed_authority: Pubkey`
+/// (32 bytes) + `authority: Pubkey` (32 bytes).
+pub const AUTH_BUFFER_HEADER_SIZE: usize = 73;
+
+/// `bump_seed: u8` (1 byte) + `price: u64` (8 bytes) + `authority: Pubkey` (32 bytes).
+pub const VENDING_MACHINE_HEADER_SIZE: usize = 41;
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct AuthorizedBufferHeader {
+    pub bump_seed: u8,
+    pub buffer_seed: u64,
+    /// The key the PDA was originally derived from. Immutable: seeds can never
+    /// change without changing the account's address, so this stays fixed even
+    /// after `authority` is reassigned via `SetAuthority`.
+    pub seed_authority: Pubkey,
+    /// The key currently allowed to write to / resize / close this buffer.
+    pub authority: Pubkey,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct VendingMachineBufferHeader {
+    pub bump_seed: u8,
+    pub price: u64,
+    pub authority: Pubkey,
+}